@@ -0,0 +1,39 @@
+use std::fmt::{self, Display, Formatter};
+
+/// Errors that can occur while allocating or protecting a secret's
+/// backing memory.
+///
+/// These are the failures that `new`/`random`/`zero`/`From` would
+/// otherwise paper over by panicking; the `try_*` variants
+/// on [`SecretVec`](crate::SecretVec) surface them instead, so a
+/// long-running process can reject one request rather than crash
+/// entirely.
+#[derive(Debug)]
+pub enum SecretError {
+    /// The backing allocation itself could not be made.
+    Allocation,
+
+    /// The allocation succeeded, but it could not be locked into
+    /// physical memory with `mlock(2)`, e.g. because the process is
+    /// over its `RLIMIT_MEMLOCK`.
+    Lock,
+
+    /// The allocation and `mlock(2)` succeeded, but the guard pages
+    /// surrounding it could not be protected with `mprotect(2)`.
+    Protect,
+}
+
+impl Display for SecretError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Allocation => write!(f, "failed to allocate secret memory"),
+            Self::Lock       => write!(f, "failed to mlock(2) secret memory"),
+            Self::Protect    => write!(f, "failed to mprotect(2) secret memory"),
+        }
+    }
+}
+
+impl std::error::Error for SecretError {}
+
+/// A convenience alias for results fallible for a [`SecretError`].
+pub type Result<T> = std::result::Result<T, SecretError>;
@@ -0,0 +1,164 @@
+//! `std::io::Read`/`Write` adapters for streaming bytes into and out of
+//! a [`SecretVec<u8>`](crate::SecretVec) without an intermediate
+//! unprotected buffer.
+
+use crate::secret_vec::{Ref, SecretVec};
+
+use std::io::{self, Read, Write};
+
+/// Reads the plaintext contents of a borrowed `SecretVec<u8>`.
+///
+/// Returned by [`SecretVec::reader`]. Bytes are read directly out of
+/// the guarded, `mlock`ed allocation; nothing is copied to an
+/// unprotected buffer until the caller's own `buf` is filled.
+pub struct Reader<'a> {
+    secret: Ref<'a, u8>,
+    pos:    usize,
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(secret: Ref<'a, u8>) -> Self {
+        Self { secret, pos: 0 }
+    }
+}
+
+impl Read for Reader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut remaining = &self.secret[self.pos..];
+        let n = remaining.read(buf)?;
+
+        self.pos += n;
+
+        Ok(n)
+    }
+}
+
+/// Assembles a secret of unknown length from streamed bytes, entirely
+/// in protected, `mlock`ed storage.
+///
+/// This lets data be piped in from a socket, a `base64` decoder, or a
+/// deserializer straight into guarded memory, without ever landing in
+/// an ordinary `Vec` or array first. Call [`finish`](Self::finish) once
+/// every byte has been written to get back a `SecretVec<u8>` sized to
+/// exactly what was written.
+///
+/// Growing means allocating a new, larger guarded region, copying the
+/// bytes written so far into it, and letting the old one zero itself
+/// on drop — the bytes are never exposed unprotected in between, but
+/// every growth does cost a fresh `mlock` and a copy. If the final
+/// size is known ahead of time, prefer building the `SecretVec`
+/// directly and borrowing it instead.
+pub struct Writer {
+    secret: SecretVec<u8>,
+    len:    usize,
+}
+
+impl Writer {
+    /// Starts a new, empty `Writer`.
+    pub fn new() -> Self {
+        Self { secret: SecretVec::zero(0), len: 0 }
+    }
+
+    /// Finalizes the bytes written so far into a `SecretVec<u8>`,
+    /// trimmed to exactly the number of bytes written.
+    pub fn finish(self) -> SecretVec<u8> {
+        if self.secret.len() == self.len {
+            return self.secret;
+        }
+
+        let mut trimmed = SecretVec::zero(self.len);
+
+        trimmed.borrow_mut().clone_from_slice(&self.secret.borrow()[..self.len]);
+
+        trimmed
+    }
+
+    /// Grows the backing allocation, if necessary, to fit `additional`
+    /// more bytes.
+    fn grow(&mut self, additional: usize) {
+        let required = self.len + additional;
+
+        if required <= self.secret.len() {
+            return;
+        }
+
+        let capacity = (self.secret.len() * 2).max(required).max(16);
+        let mut grown = SecretVec::zero(capacity);
+
+        grown.borrow_mut()[..self.len].clone_from_slice(&self.secret.borrow()[..self.len]);
+
+        self.secret = grown;
+    }
+}
+
+impl Default for Writer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Write for Writer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.grow(buf.len());
+
+        let end = self.len + buf.len();
+
+        self.secret.borrow_mut()[self.len..end].clone_from_slice(buf);
+        self.len = end;
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl SecretVec<u8> {
+    /// Returns an [`std::io::Read`]er over this secret's current
+    /// contents.
+    pub fn reader(&self) -> Reader<'_> {
+        Reader::new(self.borrow())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::{Read, Write};
+
+    #[test]
+    fn it_writes_into_protected_storage() {
+        let mut writer = Writer::new();
+
+        let n = writer.write(&[1, 2, 3, 4, 5]).unwrap();
+        let secret = writer.finish();
+
+        assert_eq!(n, 5);
+        assert_eq!(*secret.borrow(), [1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn it_grows_past_its_initial_capacity() {
+        let mut writer = Writer::new();
+
+        for byte in 0..32u8 {
+            writer.write_all(&[byte]).unwrap();
+        }
+
+        let secret = writer.finish();
+
+        assert_eq!(secret.len(), 32);
+        assert_eq!(*secret.borrow(), (0..32u8).collect::<Vec<_>>()[..]);
+    }
+
+    #[test]
+    fn it_reads_out_the_plaintext() {
+        let secret = SecretVec::<u8>::from(&mut [1, 2, 3][..]);
+        let mut out = [0u8; 3];
+
+        secret.reader().read_exact(&mut out).unwrap();
+
+        assert_eq!(out, [1, 2, 3]);
+    }
+}
@@ -0,0 +1,92 @@
+//! Optional [`serde`] support for [`SecretVec<u8>`](crate::SecretVec),
+//! enabled via the `serde` feature.
+//!
+//! `#[derive(Serialize, Deserialize)]` isn't an option here: the
+//! derived impls would copy the protected bytes into buffers serde
+//! doesn't know (or care) to zero. These impls instead borrow the
+//! secret and hand the serializer its bytes directly, and on the way
+//! back, allocate the guarded storage up front and write incoming
+//! bytes straight into it.
+//!
+//! This can't make the *serializer's* side unprotected-copy-free — a
+//! binary format like `bincode` will have the encoded/decoded bytes
+//! sitting in its own buffer at some point, which is outside this
+//! crate's control — but it does guarantee `SecretVec` itself never
+//! holds more than the one, guarded copy.
+
+#![cfg(feature = "serde")]
+
+use crate::io::Writer;
+use crate::secret_vec::SecretVec;
+
+use std::fmt;
+use std::io::Write;
+
+use serde::de::{self, Deserializer, SeqAccess, Visitor};
+use serde::ser::Serializer;
+use serde::{Deserialize, Serialize};
+
+impl Serialize for SecretVec<u8> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: Serializer {
+        serializer.serialize_bytes(self.borrow().as_ref())
+    }
+}
+
+struct SecretVecVisitor;
+
+impl<'de> Visitor<'de> for SecretVecVisitor {
+    type Value = SecretVec<u8>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a byte sequence")
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+    where E: de::Error {
+        let mut secret = SecretVec::zero(v.len());
+
+        secret.borrow_mut().clone_from_slice(v);
+
+        Ok(secret)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where A: SeqAccess<'de> {
+        // some formats (e.g. `serde_json`) drive byte arrays through
+        // `visit_seq` one element at a time rather than handing us a
+        // contiguous slice. A plain `Vec<u8>` would reallocate as it
+        // grows, leaving stale, un-zeroed plaintext behind in every
+        // buffer it outgrows; `Writer` grows the same way but zeroes
+        // each buffer it outgrows, so the plaintext only ever lives in
+        // guarded storage.
+        let mut writer = Writer::new();
+
+        while let Some(byte) = seq.next_element()? {
+            writer.write_all(&[byte]).map_err(de::Error::custom)?;
+        }
+
+        Ok(writer.finish())
+    }
+}
+
+impl<'de> Deserialize<'de> for SecretVec<u8> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: Deserializer<'de> {
+        deserializer.deserialize_bytes(SecretVecVisitor)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_round_trips_through_bincode() {
+        let secret     = SecretVec::<u8>::from(&mut [1, 2, 3, 4][..]);
+        let bytes      = bincode::serialize(&secret).unwrap();
+        let round_trip: SecretVec<u8> = bincode::deserialize(&bytes).unwrap();
+
+        assert_eq!(secret, round_trip);
+    }
+}
@@ -0,0 +1,331 @@
+use crate::boxed::Box;
+use crate::traits::*;
+
+use std::cell::UnsafeCell;
+use std::fmt::{self, Debug, Formatter};
+use std::ops::{Deref, DerefMut};
+use std::sync::Mutex;
+
+/// The `state` value of a [`SharedSecret`] that has an outstanding
+/// mutable borrow. Any other value is the count of outstanding
+/// immutable borrows (zero meaning none).
+const WRITER: isize = isize::MIN;
+
+///
+/// A `Sync`-capable sibling of [`SecretVec`] for secrets that are
+/// shared, immutably, across threads.
+///
+/// `SecretVec::borrow_mut` takes `&mut self`, which statically
+/// guarantees exclusivity but also means a `SecretVec` behind an `Arc`
+/// can never be borrowed at all from more than one thread — even
+/// though `PROT_READ` happily permits multiple concurrent readers.
+/// `SharedSecret` lifts that restriction: `borrow()` and `borrow_mut()`
+/// both take `&self`, and exclusivity is instead checked at runtime
+/// with a mutex-guarded counter, much like a thread-safe `RefCell`.
+/// No atomic compare-exchange is used for the borrow count.
+///
+/// The counter doubles as the source of truth for when the underlying
+/// memory is `mprotect`ed: it transitions to `PROT_READ` exactly once,
+/// on the 0 → 1 reader transition, and back to `PROT_NONE` exactly
+/// once, on the matching 1 → 0 transition (and likewise `PROT_WRITE`
+/// for the lone writer). The counter is held behind a [`Mutex`] rather
+/// than a bare atomic precisely so that a boundary transition and its
+/// `mprotect` call happen as one critical section: an atomic
+/// compare-exchange can only order the counter update itself, not the
+/// system call next to it, so a lock-free version of this type would
+/// let one thread publish "readers: 1" before its `unlock()` actually
+/// runs, during which a second thread could see the already-published
+/// count, skip its own `unlock()`, and dereference memory still set to
+/// `PROT_NONE`. Holding the mutex across both the `mprotect` call and
+/// the count update closes that window; the lock is only ever held for
+/// that one bookkeeping step, not for the lifetime of a borrow, so
+/// concurrent readers don't block each other while actually using the
+/// secret.
+///
+/// Unlike `SecretVec`, conflicting borrows aren't caught by the borrow
+/// checker; they're caught at runtime, just like `RefCell`, and
+/// `borrow`/`borrow_mut` panic when they conflict. Use `try_borrow`/
+/// `try_borrow_mut` to handle that case without panicking.
+///
+/// [`SecretVec`]: crate::SecretVec
+///
+pub struct SharedSecret<T: Bytes> {
+    boxed: UnsafeCell<Box<T>>,
+    state: Mutex<isize>,
+}
+
+// SAFETY: all access to `boxed` is gated by `state`; every borrow of
+// `boxed` and the bookkeeping that admits it (the `mprotect` call and
+// the counter update that publishes it) happen inside the same `state`
+// critical section, so no thread can observe a borrow count that
+// doesn't yet match the protection state. `Sync` additionally hands
+// out `&[T]` to multiple threads at once via concurrent `SharedRef`s,
+// so it needs `T: Sync` too, not just `T: Send` — the same bound
+// `RwLock<T>` requires of its contents for the same reason.
+unsafe impl<T: Bytes + Send> Send for SharedSecret<T> {}
+unsafe impl<T: Bytes + Send + Sync> Sync for SharedSecret<T> {}
+
+pub struct SharedRef<'a, T: Bytes> {
+    secret: &'a SharedSecret<T>,
+}
+
+pub struct SharedRefMut<'a, T: Bytes> {
+    secret: &'a SharedSecret<T>,
+}
+
+/// The error returned by [`SharedSecret::try_borrow`] when the secret
+/// is already mutably borrowed.
+#[derive(Debug)]
+pub struct BorrowError(());
+
+/// The error returned by [`SharedSecret::try_borrow_mut`] when the
+/// secret is already borrowed, mutably or immutably.
+#[derive(Debug)]
+pub struct BorrowMutError(());
+
+impl fmt::Display for BorrowError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "SharedSecret is already mutably borrowed")
+    }
+}
+
+impl fmt::Display for BorrowMutError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "SharedSecret is already borrowed")
+    }
+}
+
+impl std::error::Error for BorrowError {}
+impl std::error::Error for BorrowMutError {}
+
+impl<T: Bytes> SharedSecret<T> {
+    pub fn new<F>(len: usize, f: F) -> Self where F: FnOnce(&mut [T]) {
+        Self { boxed: UnsafeCell::new(Box::new(len, f)), state: Mutex::new(0) }
+    }
+
+    pub fn len(&self) -> usize {
+        // SAFETY: `len` never depends on the current protection state.
+        unsafe { (*self.boxed.get()).len() }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        unsafe { (*self.boxed.get()).is_empty() }
+    }
+
+    pub fn size(&self) -> usize {
+        unsafe { (*self.boxed.get()).size() }
+    }
+
+    /// Immutably borrows the secret, blocking out any mutable borrow
+    /// until the returned [`SharedRef`] (and every other outstanding
+    /// `SharedRef`) is dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the secret is currently mutably borrowed. Use
+    /// [`try_borrow`](Self::try_borrow) to handle this without
+    /// panicking.
+    pub fn borrow(&self) -> SharedRef<'_, T> {
+        self.try_borrow().expect("SharedSecret already mutably borrowed")
+    }
+
+    /// Mutably borrows the secret, failing if any borrow (mutable or
+    /// immutable) is already outstanding.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the secret is already borrowed. Use
+    /// [`try_borrow_mut`](Self::try_borrow_mut) to handle this without
+    /// panicking.
+    pub fn borrow_mut(&self) -> SharedRefMut<'_, T> {
+        self.try_borrow_mut().expect("SharedSecret already borrowed")
+    }
+
+    pub fn try_borrow(&self) -> Result<SharedRef<'_, T>, BorrowError> {
+        let mut readers = self.state.lock().unwrap();
+
+        if *readers < 0 {
+            return Err(BorrowError(()));
+        }
+
+        // the 0 -> 1 transition is the only one that needs to touch
+        // the protection state; every other increment joins readers
+        // that already hold it at `PROT_READ`. Doing the `mprotect`
+        // call and the count update under the same lock guard means no
+        // other thread can observe the new count before `unlock()` has
+        // actually run.
+        if *readers == 0 {
+            unsafe { (*self.boxed.get()).unlock(); }
+        }
+
+        *readers += 1;
+
+        Ok(SharedRef { secret: self })
+    }
+
+    pub fn try_borrow_mut(&self) -> Result<SharedRefMut<'_, T>, BorrowMutError> {
+        let mut state = self.state.lock().unwrap();
+
+        if *state != 0 {
+            return Err(BorrowMutError(()));
+        }
+
+        unsafe { (*self.boxed.get()).unlock_mut(); }
+
+        *state = WRITER;
+
+        Ok(SharedRefMut { secret: self })
+    }
+}
+
+impl<T: Bytes + Randomizable> SharedSecret<T> {
+    pub fn random(len: usize) -> Self {
+        Self { boxed: UnsafeCell::new(Box::random(len)), state: Mutex::new(0) }
+    }
+}
+
+impl<T: Bytes + Zeroable> SharedSecret<T> {
+    pub fn zero(len: usize) -> Self {
+        Self { boxed: UnsafeCell::new(Box::zero(len)), state: Mutex::new(0) }
+    }
+}
+
+impl<T: Bytes + Zeroable> From<&mut [T]> for SharedSecret<T> {
+    fn from(data: &mut [T]) -> Self {
+        Self { boxed: UnsafeCell::new(data.into()), state: Mutex::new(0) }
+    }
+}
+
+impl<T: Bytes> Debug for SharedSecret<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        unsafe { (*self.boxed.get()).fmt(f) }
+    }
+}
+
+impl<T: Bytes> Drop for SharedRef<'_, T> {
+    fn drop(&mut self) {
+        let mut readers = self.secret.state.lock().unwrap();
+
+        *readers -= 1;
+
+        // the matching 1 -> 0 transition is the only one that needs
+        // to touch the protection state; it happens under the same
+        // lock guard as the decrement, so no fresh 0 -> 1 borrow can
+        // observe the count hitting zero and race our `lock()` call.
+        if *readers == 0 {
+            unsafe { (*self.secret.boxed.get()).lock(); }
+        }
+    }
+}
+
+impl<T: Bytes> Deref for SharedRef<'_, T> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { (*self.secret.boxed.get()).as_ref() }
+    }
+}
+
+impl<T: Bytes> Debug for SharedRef<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { self.secret.fmt(f) }
+}
+
+impl<T: Bytes> PartialEq for SharedRef<'_, T> {
+    fn eq(&self, rhs: &Self) -> bool {
+        self.as_ref().constant_eq(rhs.as_ref())
+    }
+}
+
+impl<T: Bytes> Drop for SharedRefMut<'_, T> {
+    fn drop(&mut self) {
+        let mut state = self.secret.state.lock().unwrap();
+
+        unsafe { (*self.secret.boxed.get()).lock(); }
+
+        *state = 0;
+    }
+}
+
+impl<T: Bytes> Deref for SharedRefMut<'_, T> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { (*self.secret.boxed.get()).as_ref() }
+    }
+}
+
+impl<T: Bytes> DerefMut for SharedRefMut<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { (*self.secret.boxed.get()).as_mut() }
+    }
+}
+
+impl<T: Bytes> Debug for SharedRefMut<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { self.secret.fmt(f) }
+}
+
+impl<T: Bytes> PartialEq for SharedRefMut<'_, T> {
+    fn eq(&self, rhs: &Self) -> bool {
+        self.as_ref().constant_eq(rhs.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_allows_concurrent_immutable_borrows() {
+        let secret = SharedSecret::<u8>::zero(4);
+        let a      = secret.borrow();
+        let b      = secret.borrow();
+
+        assert_eq!(*a, [0, 0, 0, 0]);
+        assert_eq!(*b, [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn it_refuses_a_mutable_borrow_while_reading() {
+        let secret = SharedSecret::<u8>::zero(4);
+        let _read  = secret.borrow();
+
+        assert!(secret.try_borrow_mut().is_err());
+    }
+
+    #[test]
+    fn it_refuses_an_immutable_borrow_while_writing() {
+        let secret = SharedSecret::<u8>::zero(4);
+        let _write = secret.borrow_mut();
+
+        assert!(secret.try_borrow().is_err());
+    }
+
+    #[test]
+    fn it_allows_a_new_borrow_after_the_old_one_drops() {
+        let secret = SharedSecret::<u8>::zero(4);
+
+        {
+            let mut w = secret.borrow_mut();
+            w.clone_from_slice(&[1, 2, 3, 4][..]);
+        }
+
+        assert_eq!(*secret.borrow(), [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn it_is_usable_across_threads() {
+        use std::sync::Arc;
+
+        let secret = Arc::new(SharedSecret::<u8>::zero(4));
+        let handles: Vec<_> = (0..4).map(|_| {
+            let secret = Arc::clone(&secret);
+            std::thread::spawn(move || {
+                let _r = secret.borrow();
+            })
+        }).collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+    }
+}